@@ -125,6 +125,13 @@ fn main() {
         } else {
             cmd.arg("-C").arg(format!("debug-assertions={}", debug_assertions));
         }
+
+        // Set the program interpreter of executables, but not of dylibs.
+        if let Some(dynamic_linker) = env::var_os("RUSTC_DYNAMIC_LINKER") {
+            if let Some(arg) = bootstrap::util::dynamic_linker_arg(&args, &dynamic_linker) {
+                cmd.arg("-C").arg(arg);
+            }
+        }
     } else {
         // FIXME(rust-lang/cargo#5754) we shouldn't be using special env vars
         // here, but rather Cargo should know what flags to pass rustc itself.
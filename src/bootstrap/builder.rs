@@ -397,6 +397,7 @@ impl<'a> Builder<'a> {
                 test::RustdocJSNotStd,
                 test::RustdocTheme,
                 test::RustdocUi,
+                test::VendorLink,
                 // Run bootstrap close to the end as it's unlikely to fail
                 test::Bootstrap,
                 // Run run-make last, since these won't pass without make on Windows
@@ -440,7 +441,8 @@ impl<'a> Builder<'a> {
                 dist::LlvmTools,
                 dist::Lldb,
                 dist::Extended,
-                dist::HashSign
+                dist::HashSign,
+                dist::TargetSpecs
             ),
             Kind::Install => describe!(
                 install::Docs,
@@ -878,7 +880,7 @@ impl<'a> Builder<'a> {
         // Only clear out the directory if we're compiling std; otherwise, we
         // should let Cargo take care of things for us (via depdep info)
         if !self.config.dry_run && mode == Mode::Std && cmd == "build" {
-            self.clear_if_dirty(&out_dir, &self.rustc(compiler));
+            self.clear_std_if_dirty(&out_dir, compiler, target);
         }
 
         // Customize the compiler we're running. Specify the compiler to cargo
@@ -1009,6 +1011,23 @@ impl<'a> Builder<'a> {
             cargo.env("RUSTC_HOST_CRT_STATIC", x.to_string());
         }
 
+        // Only executables get the dynamic linker, see `util::dynamic_linker_arg`.
+        if let Some(dynamic_linker) = self.dynamic_linker(target) {
+            cargo.env("RUSTC_DYNAMIC_LINKER", dynamic_linker);
+        }
+
+        // Pre-link args from `-Z` come after the ones from the target spec, so
+        // this undoes the `--as-needed` that Linux targets pass by default.
+        if self.no_as_needed(target) {
+            rustflags.arg("-Zpre-link-arg=-Wl,--no-as-needed");
+        }
+
+        if let Mode::Std | Mode::Rustc | Mode::Codegen = mode {
+            if let Some(cpu) = self.target_cpu(target) {
+                rustflags.arg(&format!("-Ctarget-cpu={}", cpu));
+            }
+        }
+
         if let Some(map) = self.build.debuginfo_map(GitRepo::Rustc) {
             cargo.env("RUSTC_DEBUGINFO_MAP", map);
         }
@@ -1252,6 +1271,18 @@ impl<'a> Builder<'a> {
         Cargo { command: cargo, rustflags }
     }
 
+    /// Clears the std output directory `out_dir` if either `compiler` or the
+    /// specification of `target` changed since std was last built there.
+    fn clear_std_if_dirty(&self, out_dir: &Path, compiler: Compiler, target: Interned<String>) {
+        self.clear_if_dirty(out_dir, &self.rustc(compiler));
+
+        // Built-in target specs are covered by the check above as they're
+        // part of rustc, but custom specs are read from disk each time.
+        if target.ends_with(".json") {
+            self.clear_if_dirty(out_dir, Path::new(&*target));
+        }
+    }
+
     /// Ensure that a given step is built, returning its output. This will
     /// cache the step, so it is safe (and good!) to call this as often as
     /// needed to ensure that all dependencies are built.
@@ -1305,7 +1336,7 @@ impl<'a> Builder<'a> {
 }
 
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
 #[derive(Debug)]
 struct Rustflags(String);
@@ -1,10 +1,11 @@
 use super::*;
-use crate::config::Config;
+use crate::config::{Config, Target};
 use std::thread;
 
+use filetime::FileTime;
 use pretty_assertions::assert_eq;
 
-fn configure(host: &[&str], target: &[&str]) -> Config {
+pub(crate) fn configure(host: &[&str], target: &[&str]) -> Config {
     let mut config = Config::default_opts();
     // don't save toolstates
     config.save_toolstates = None;
@@ -15,6 +16,7 @@ fn configure(host: &[&str], target: &[&str]) -> Config {
         .out
         .join("tmp-rustbuild-tests")
         .join(&thread::current().name().unwrap_or("unknown").replace(":", "-"));
+    let _ = fs::remove_dir_all(&dir);
     t!(fs::create_dir_all(&dir));
     config.out = dir;
     config.build = INTERNER.intern_str("A");
@@ -31,6 +33,15 @@ fn configure(host: &[&str], target: &[&str]) -> Config {
     config
 }
 
+/// Writes the shell `script` to `path` as an executable standing in for a tool.
+#[cfg(unix)]
+pub(crate) fn fake_executable(path: &Path, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    t!(fs::write(path, script));
+    t!(fs::set_permissions(path, fs::Permissions::from_mode(0o755)));
+}
+
 fn first<A, B>(v: Vec<(A, B)>) -> Vec<A> {
     v.into_iter().map(|(a, _)| a).collect::<Vec<_>>()
 }
@@ -414,3 +425,221 @@ fn test_exclude() {
     // Ensure other tests are not affected.
     assert!(builder.cache.contains::<test::RustdocUi>());
 }
+
+#[test]
+fn vendor_musl_links_dynamically() {
+    let gentoo = INTERNER.intern_str("i586-gentoo-linux-musl");
+    let upstream = INTERNER.intern_str("i586-unknown-linux-musl");
+
+    // There's no musl-root here, which sanity checks only accept for musl
+    // targets linking dynamically.
+    let mut config = configure(&[], &["i586-gentoo-linux-musl"]);
+    config.target_config.insert(gentoo, Target { crt_static: Some(false), ..Default::default() });
+    let build = Build::new(config);
+    assert!(build.supports_dynamic_musl(gentoo));
+    assert!(!build.supports_dynamic_musl(upstream));
+
+    let builder = Builder::new(&build);
+    let compiler = Compiler { host: INTERNER.intern_str("A"), stage: 1 };
+    let cargo = builder.cargo(compiler, Mode::Std, gentoo, "build");
+    assert!(rustflags(&cargo).contains(&"-Ctarget-feature=-crt-static"));
+}
+
+#[test]
+fn custom_musl_spec_links_dynamically() {
+    let build = Build::new(configure(&[], &[]));
+    let dir = build.out.join("custom-musl-spec");
+    t!(fs::create_dir_all(&dir));
+
+    // Only what matters here of the specs `--print target-spec-json` gives.
+    let gentoo = dir.join("i586-gentoo-linux-musl.json");
+    t!(fs::write(&gentoo, r#"{"env": "musl", "crt-static-default": false}"#));
+    let upstream = dir.join("i586-unknown-linux-musl.json");
+    t!(fs::write(&upstream, r#"{"env": "musl", "crt-static-default": true}"#));
+    let gnu = dir.join("i586-gentoo-linux-gnu.json");
+    t!(fs::write(&gnu, r#"{"env": "gnu", "crt-static-default": false}"#));
+
+    let target = |path: &Path| INTERNER.intern_string(path.to_str().unwrap().to_string());
+    assert!(build.supports_dynamic_musl(target(&gentoo)));
+    assert_eq!(build.crt_static(target(&gentoo)), Some(false));
+    assert!(!build.supports_dynamic_musl(target(&upstream)));
+    assert_eq!(build.crt_static(target(&upstream)), None);
+    assert!(!build.supports_dynamic_musl(target(&gnu)));
+    assert_eq!(build.crt_static(target(&gnu)), None);
+}
+
+fn rustflags(cargo: &Cargo) -> Vec<&str> {
+    cargo.rustflags.0.split(' ').collect()
+}
+
+#[test]
+fn no_as_needed_override() {
+    let a = INTERNER.intern_str("A");
+    let b = INTERNER.intern_str("B");
+    let mut config = configure(&[], &["B"]);
+    config.target_config.insert(b, Target { no_as_needed: true, ..Default::default() });
+    let build = Build::new(config);
+    let builder = Builder::new(&build);
+
+    let compiler = Compiler { host: a, stage: 1 };
+    let flag = "-Zpre-link-arg=-Wl,--no-as-needed";
+    assert!(rustflags(&builder.cargo(compiler, Mode::Std, b, "build")).contains(&flag));
+    assert!(!rustflags(&builder.cargo(compiler, Mode::Std, a, "build")).contains(&flag));
+}
+
+#[test]
+fn target_cpu_override() {
+    let a = INTERNER.intern_str("A");
+    let b = INTERNER.intern_str("B");
+    let mut config = configure(&[], &["B"]);
+    let cpu = Some("pentium4".to_string());
+    config.target_config.insert(b, Target { target_cpu: cpu, ..Default::default() });
+    let build = Build::new(config);
+    let builder = Builder::new(&build);
+
+    let compiler = Compiler { host: a, stage: 1 };
+    let flag = "-Ctarget-cpu=pentium4";
+    for &mode in &[Mode::Std, Mode::Rustc] {
+        assert!(rustflags(&builder.cargo(compiler, mode, b, "build")).contains(&flag));
+    }
+    // Only the standard library and the compiler are tuned for the CPU.
+    for &mode in &[Mode::ToolStd, Mode::ToolRustc] {
+        assert!(!rustflags(&builder.cargo(compiler, mode, b, "build")).contains(&flag));
+    }
+    assert!(!rustflags(&builder.cargo(compiler, Mode::Std, a, "build")).contains(&flag));
+}
+
+#[test]
+fn llvm_libunwind_override() {
+    let a = INTERNER.intern_str("A");
+    let b = INTERNER.intern_str("B");
+    let c = INTERNER.intern_str("C");
+    let mut config = configure(&[], &["B", "C"]);
+    config.target_config.insert(b, Target { llvm_libunwind: Some(true), ..Default::default() });
+    config.target_config.insert(c, Target { llvm_libunwind: Some(false), ..Default::default() });
+    let build = Build::new(config);
+
+    let uses_libunwind = |build: &Build, target| {
+        build.std_features(target).split(' ').any(|feature| feature == "llvm-libunwind")
+    };
+    assert!(uses_libunwind(&build, b));
+    assert!(!uses_libunwind(&build, c));
+    // Targets without an override follow `rust.llvm-libunwind`.
+    assert!(!uses_libunwind(&build, a));
+
+    let mut config = configure(&[], &["B", "C"]);
+    config.llvm_libunwind = true;
+    config.target_config.insert(c, Target { llvm_libunwind: Some(false), ..Default::default() });
+    let build = Build::new(config);
+    assert!(uses_libunwind(&build, a));
+    assert!(uses_libunwind(&build, b));
+    assert!(!uses_libunwind(&build, c));
+}
+
+#[test]
+fn custom_target_spec_invalidates_std() {
+    let build = Build::new(configure(&[], &[]));
+    let builder = Builder::new(&build);
+    let compiler = Compiler { host: build.build, stage: 1 };
+
+    let dir = build.out.join("custom-target-spec");
+    let _ = fs::remove_dir_all(&dir);
+    let out_dir = dir.join("stage1-std");
+    let spec = dir.join("x86_64-foxkit-linux-musl.json");
+    t!(fs::create_dir_all(&out_dir));
+    t!(fs::write(&spec, r#"{"crt-static-default": false}"#));
+    t!(filetime::set_file_mtime(&spec, FileTime::from_unix_time(1_000, 0)));
+    let target = INTERNER.intern_string(spec.to_str().unwrap().to_string());
+
+    builder.clear_std_if_dirty(&out_dir, compiler, target);
+    let libstd = out_dir.join("libstd-0123abcd.rlib");
+    t!(fs::write(&libstd, ""));
+    builder.clear_std_if_dirty(&out_dir, compiler, target);
+    assert!(libstd.exists());
+
+    // Editing the spec after std was built throws away the old std.
+    t!(fs::write(&spec, r#"{"crt-static-default": true}"#));
+    let stamp = FileTime::from_last_modification_time(&t!(fs::metadata(out_dir.join(".stamp"))));
+    let edited = FileTime::from_unix_time(stamp.unix_seconds() + 60, 0);
+    t!(filetime::set_file_mtime(&spec, edited));
+    builder.clear_std_if_dirty(&out_dir, compiler, target);
+    assert!(!libstd.exists());
+}
+
+#[test]
+fn keep_stage_std() {
+    let a = INTERNER.intern_str("A");
+    let compiler = Compiler { host: a, stage: 1 };
+
+    let build = Build::new(configure(&[], &[]));
+    let mut builder = Builder::new(&build);
+    builder.ensure(compile::Std { compiler, target: a });
+    assert_eq!(
+        first(builder.cache.all::<compile::StartupObjects>()),
+        &[compile::StartupObjects { compiler, target: a }]
+    );
+
+    // A dry run treats every stamp as intact, so std is kept without
+    // building anything that goes into it.
+    let mut config = configure(&[], &[]);
+    config.keep_stage_std = vec![1];
+    let build = Build::new(config);
+    let mut builder = Builder::new(&build);
+    builder.ensure(compile::Std { compiler, target: a });
+    assert!(builder.cache.all::<compile::StartupObjects>().is_empty());
+}
+
+#[test]
+fn vendor_link_needs_configured_linker() {
+    let a = INTERNER.intern_str("A");
+    let gentoo = INTERNER.intern_str("x86_64-gentoo-linux-musl");
+    let compiler = Compiler { host: a, stage: 1 };
+    let std = compile::Std { compiler, target: gentoo };
+
+    // The C compiler detected for the target isn't good enough to link with.
+    let mut config = configure(&[], &["x86_64-gentoo-linux-musl"]);
+    config.target_config.insert(gentoo, Target { crt_static: Some(false), ..Default::default() });
+    let build = Build::new(config);
+    let mut builder = Builder::new(&build);
+    builder.ensure(test::VendorLink { compiler, target: gentoo });
+    assert!(!first(builder.cache.all::<compile::Std>()).contains(&std));
+
+    let mut config = configure(&[], &["x86_64-gentoo-linux-musl"]);
+    let linker = Some(PathBuf::from("x86_64-gentoo-linux-musl-gcc"));
+    let target = Target { linker, crt_static: Some(false), ..Default::default() };
+    config.target_config.insert(gentoo, target);
+    let build = Build::new(config);
+    let mut builder = Builder::new(&build);
+    builder.ensure(test::VendorLink { compiler, target: gentoo });
+    assert!(first(builder.cache.all::<compile::Std>()).contains(&std));
+}
+
+#[test]
+fn vendor_link_args_use_the_configured_linkers() {
+    let gentoo = INTERNER.intern_str("x86_64-gentoo-linux-musl");
+    let mut config = configure(&[], &["x86_64-gentoo-linux-musl"]);
+    let linker = PathBuf::from("x86_64-gentoo-linux-musl-gcc");
+    let dynamic_linker = Some(PathBuf::from("/lib/ld-musl-x86_64.so.1"));
+    let target = Target {
+        linker: Some(linker.clone()),
+        dynamic_linker,
+        crt_static: Some(false),
+        ..Default::default()
+    };
+    config.target_config.insert(gentoo, target);
+    let build = Build::new(config);
+    let builder = Builder::new(&build);
+
+    let args =
+        test::vendor_link_args(&builder, gentoo, &linker, Path::new("main.rs"), Path::new("main"));
+    let expected: Vec<std::ffi::OsString> = vec![
+        "--target".into(),
+        "x86_64-gentoo-linux-musl".into(),
+        "-Clinker=x86_64-gentoo-linux-musl-gcc".into(),
+        "-Clink-arg=-Wl,-dynamic-linker,/lib/ld-musl-x86_64.so.1".into(),
+        "-o".into(),
+        "main".into(),
+        "main.rs".into(),
+    ];
+    assert_eq!(args, expected);
+}
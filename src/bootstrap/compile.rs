@@ -8,21 +8,23 @@
 
 use std::borrow::Cow;
 use std::env;
+use std::ffi::OsString;
 use std::fs;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::process::{exit, Command, Stdio};
 use std::str;
+use std::time::Instant;
 
-use build_helper::{output, t, up_to_date};
+use build_helper::{mtime, output, t};
 use filetime::FileTime;
 use serde::Deserialize;
 
 use crate::builder::Cargo;
 use crate::dist;
 use crate::native;
-use crate::util::{exe, is_dylib};
+use crate::util::{exe, is_dylib, timeit};
 use crate::{Compiler, GitRepo, Mode};
 
 use crate::builder::{Builder, Kind, RunConfig, ShouldRun, Step};
@@ -64,6 +66,18 @@ impl Step for Std {
             return;
         }
 
+        if builder.config.keep_stage_std.contains(&compiler.stage) {
+            if stamp_is_intact(builder, &libstd_stamp(builder, compiler, target)) {
+                builder.info("Warning: Using a potentially old libstd. This may not behave well.");
+                builder.ensure(StdLink { compiler, target_compiler: compiler, target });
+                return;
+            }
+            builder.info(&format!(
+                "Not keeping stage{} std for {}: its stamp or the files it lists are missing",
+                compiler.stage, target
+            ));
+        }
+
         let mut target_deps = builder.ensure(StartupObjects { compiler, target });
 
         let compiler_to_use = builder.compiler_for(compiler.stage, compiler.host, target);
@@ -85,6 +99,12 @@ impl Step for Std {
 
         target_deps.extend(copy_third_party_objects(builder, &compiler, target).into_iter());
 
+        if compiler.stage == 0 {
+            if let Err(e) = check_stage0_knows_target(builder, target) {
+                panic!("{}", e);
+            }
+        }
+
         let mut cargo = builder.cargo(compiler, Mode::Std, target, "build");
         std_cargo(builder, target, &mut cargo);
 
@@ -92,13 +112,14 @@ impl Step for Std {
             "Building stage{} std artifacts ({} -> {})",
             compiler.stage, &compiler.host, target
         ));
-        run_cargo(
+        run_timed_cargo(
             builder,
+            "std",
+            compiler,
+            target,
             cargo,
-            vec![],
             &libstd_stamp(builder, compiler, target),
             target_deps,
-            false,
         );
 
         builder.ensure(StdLink {
@@ -109,6 +130,35 @@ impl Step for Std {
     }
 }
 
+/// Makes sure the stage0 compiler knows about `target` before asking it to
+/// build the standard library for it.
+///
+/// This is usually the case, but when cross-compiling to a target newer than
+/// the snapshot compiler Cargo would otherwise fail with an error that doesn't
+/// point at the actual problem.
+fn check_stage0_knows_target(
+    builder: &Builder<'_>,
+    target: Interned<String>,
+) -> Result<(), String> {
+    // Custom target specifications don't need to be built into the compiler.
+    if builder.config.dry_run || target.ends_with(".json") {
+        return Ok(());
+    }
+
+    let targets = output(Command::new(&builder.initial_rustc).arg("--print").arg("target-list"));
+    if targets.lines().any(|t| t.trim() == &*target) {
+        return Ok(());
+    }
+    Err(format!(
+        "the stage0 compiler `{}` does not support the target `{}`; use a stage0 \
+         compiler that knows about it (see `build.rustc` in config.toml), or pass \
+         a custom target specification with `--target path/to/{}.json`",
+        builder.initial_rustc.display(),
+        target,
+        target
+    ))
+}
+
 /// Copies third party objects needed by various targets.
 fn copy_third_party_objects(
     builder: &Builder<'_>,
@@ -130,8 +180,9 @@ fn copy_third_party_objects(
     // Since musl supports fully static linking, we can cross link for it even
     // with a glibc-targeting toolchain, given we have the appropriate startup
     // files. As those shipped with glibc won't work, copy the ones provided by
-    // musl so we have them on linux-gnu hosts.
-    if target.contains("musl") {
+    // musl so we have them on linux-gnu hosts. Targets linking against a
+    // dynamic system musl use the system's startup objects instead.
+    if target.contains("musl") && !builder.supports_dynamic_musl(target) {
         let srcdir = builder.musl_root(target).unwrap().join("lib");
         for &obj in &["crt1.o", "crti.o", "crtn.o"] {
             copy_and_stamp(&srcdir, obj);
@@ -204,14 +255,17 @@ pub fn std_cargo(builder: &Builder<'_>, target: Interned<String>, cargo: &mut Ca
             .arg("--features")
             .arg("compiler-builtins-mem compiler-builtins-c");
     } else {
-        let mut features = builder.std_features();
+        let mut features = builder.std_features(target);
         features.push_str(&compiler_builtins_c_feature);
 
+        // libtest pulls in libstd and libproc_macro, so building it produces
+        // the full sysroot. Minimal sysroots stop at libstd.
+        let krate = if builder.minimal_sysroot(target) { "src/libstd" } else { "src/libtest" };
         cargo
             .arg("--features")
             .arg(features)
             .arg("--manifest-path")
-            .arg(builder.src.join("src/libtest/Cargo.toml"));
+            .arg(builder.src.join(krate).join("Cargo.toml"));
 
         // Help the libc crate compile by assisting it in finding various
         // sysroot native libraries.
@@ -263,8 +317,66 @@ impl Step for StdLink {
         ));
         let libdir = builder.sysroot_libdir(target_compiler, target);
         let hostdir = builder.sysroot_libdir(target_compiler, compiler.host);
-        add_to_sysroot(builder, &libdir, &hostdir, &libstd_stamp(builder, compiler, target));
+        let linked =
+            add_to_sysroot(builder, &libdir, &hostdir, &libstd_stamp(builder, compiler, target));
+        builder.record_sysroot_artifacts(target_compiler, target, Mode::Std, linked);
+        if let Err(missing) = verify_sysroot(builder, &libdir, target) {
+            eprintln!(
+                "error: the stage{} sysroot for {} is missing {} in {}",
+                target_compiler.stage,
+                target,
+                missing.join(", "),
+                libdir.display()
+            );
+            exit(1);
+        }
+    }
+}
+
+/// Checks that the sysroot of `compiler` contains the core libraries for
+/// `target`.
+///
+/// A sysroot missing any of these only fails much later, and confusingly, when
+/// something tries to link against it, so bail out with a list of what's
+/// missing as soon as the sysroot has been assembled.
+fn verify_sysroot(
+    builder: &Builder<'_>,
+    libdir: &Path,
+    target: Interned<String>,
+) -> Result<(), Vec<String>> {
+    if builder.config.dry_run {
+        return Ok(());
     }
+
+    // no_std targets only get liballoc and its dependencies, and minimal
+    // sysroots stop at libstd, see `std_cargo`.
+    let expected: &[&str] = if builder.no_std(target) == Some(true) {
+        &["core", "alloc"]
+    } else if builder.minimal_sysroot(target) {
+        &["core", "alloc", "std"]
+    } else {
+        &["core", "alloc", "std", "test"]
+    };
+
+    let missing = missing_sysroot_libs(libdir, expected);
+    if missing.is_empty() { Ok(()) } else { Err(missing) }
+}
+
+/// Returns the names of the libraries in `expected` which have no rlib in
+/// `libdir`, e.g. `libstd` if there's no `libstd-*.rlib`.
+fn missing_sysroot_libs(libdir: &Path, expected: &[&str]) -> Vec<String> {
+    let rlibs = t!(fs::read_dir(libdir))
+        .map(|f| t!(f).file_name().to_string_lossy().into_owned())
+        .filter(|filename| filename.ends_with(".rlib"))
+        .collect::<Vec<_>>();
+    expected
+        .iter()
+        .map(|krate| format!("lib{}", krate))
+        .filter(|lib| {
+            let prefix = format!("{}-", lib);
+            !rlibs.iter().any(|filename| filename.starts_with(&prefix))
+        })
+        .collect()
 }
 
 /// Copies sanitizer runtime libraries into target libdir.
@@ -303,7 +415,7 @@ fn copy_sanitizers(
     target_deps
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialOrd, Ord, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct StartupObjects {
     pub compiler: Compiler,
     pub target: Interned<String>,
@@ -346,7 +458,12 @@ impl Step for StartupObjects {
         for file in &["rsbegin", "rsend"] {
             let src_file = &src_dir.join(file.to_string() + ".rs");
             let dst_file = &dst_dir.join(file.to_string() + ".o");
-            if !up_to_date(src_file, dst_file) {
+            let dep_file = &dst_dir.join(file.to_string() + ".d");
+            if !dep_info_up_to_date(dep_file, dst_file) {
+                let mut emit = OsString::from("--emit=obj=");
+                emit.push(dst_file);
+                emit.push(",dep-info=");
+                emit.push(dep_file);
                 let mut cmd = Command::new(&builder.initial_rustc);
                 builder.run(
                     cmd.env("RUSTC_BOOTSTRAP", "1")
@@ -354,9 +471,7 @@ impl Step for StartupObjects {
                         .arg("bootstrap")
                         .arg("--target")
                         .arg(target)
-                        .arg("--emit=obj")
-                        .arg("-o")
-                        .arg(dst_file)
+                        .arg(emit)
                         .arg(src_file),
                 );
             }
@@ -442,13 +557,14 @@ impl Step for Rustc {
             "Building stage{} compiler artifacts ({} -> {})",
             compiler.stage, &compiler.host, target
         ));
-        run_cargo(
+        run_timed_cargo(
             builder,
+            "rustc",
+            compiler,
+            target,
             cargo,
-            vec![],
             &librustc_stamp(builder, compiler, target),
             vec![],
-            false,
         );
 
         // We used to build librustc_codegen_llvm as a separate step,
@@ -506,22 +622,32 @@ pub fn rustc_cargo(builder: &Builder<'_>, cargo: &mut Cargo, target: Interned<St
     rustc_cargo_env(builder, cargo, target);
 }
 
+/// Returns the version rustc and the tools report about themselves, which is
+/// `rust_version` with the vendor's `rust.version-suffix` appended.
+pub(crate) fn rustc_version(builder: &Builder<'_>) -> String {
+    let mut version = builder.rust_version();
+    if let Some(ref suffix) = builder.config.rust_version_suffix {
+        version.push_str(suffix);
+    }
+    version
+}
+
 pub fn rustc_cargo_env(builder: &Builder<'_>, cargo: &mut Cargo, target: Interned<String>) {
     // Set some configuration variables picked up by build scripts and
     // the compiler alike
     cargo
         .env("CFG_RELEASE", builder.rust_release())
         .env("CFG_RELEASE_CHANNEL", &builder.config.channel)
-        .env("CFG_VERSION", builder.rust_version())
+        .env("CFG_VERSION", rustc_version(builder))
         .env("CFG_PREFIX", builder.config.prefix.clone().unwrap_or_default());
 
     let libdir_relative = builder.config.libdir_relative().unwrap_or_else(|| Path::new("lib"));
     cargo.env("CFG_LIBDIR_RELATIVE", libdir_relative);
 
-    if let Some(ref ver_date) = builder.rust_info.commit_date() {
+    if let Some(ver_date) = builder.rust_commit_date() {
         cargo.env("CFG_VER_DATE", ver_date);
     }
-    if let Some(ref ver_hash) = builder.rust_info.sha() {
+    if let Some(ver_hash) = builder.rust_sha() {
         cargo.env("CFG_VER_HASH", ver_hash);
     }
     if !builder.unstable_features() {
@@ -602,12 +728,13 @@ impl Step for RustcLink {
             "Copying stage{} rustc from stage{} ({} -> {} / {})",
             target_compiler.stage, compiler.stage, &compiler.host, target_compiler.host, target
         ));
-        add_to_sysroot(
+        let linked = add_to_sysroot(
             builder,
             &builder.sysroot_libdir(target_compiler, target),
             &builder.sysroot_libdir(target_compiler, compiler.host),
             &librustc_stamp(builder, compiler, target),
         );
+        builder.record_sysroot_artifacts(target_compiler, target, Mode::Rustc, linked);
     }
 }
 
@@ -786,21 +913,101 @@ impl Step for Assemble {
 ///
 /// For a particular stage this will link the file listed in `stamp` into the
 /// `sysroot_dst` provided.
+///
+/// If `build.sysroot-depfiles` is enabled this also writes a make-style depfile
+/// next to `stamp` with one rule per linked file.
+///
+/// Returns the source and destination of every file linked.
 pub fn add_to_sysroot(
     builder: &Builder<'_>,
     sysroot_dst: &Path,
     sysroot_host_dst: &Path,
     stamp: &Path,
-) {
+) -> Vec<(PathBuf, PathBuf)> {
     t!(fs::create_dir_all(&sysroot_dst));
     t!(fs::create_dir_all(&sysroot_host_dst));
+    let mut linked = Vec::new();
     for (path, host) in builder.read_stamp_file(stamp) {
-        if host {
-            builder.copy(&path, &sysroot_host_dst.join(path.file_name().unwrap()));
-        } else {
-            builder.copy(&path, &sysroot_dst.join(path.file_name().unwrap()));
-        }
+        let dst = if host { sysroot_host_dst } else { sysroot_dst };
+        let dst = dst.join(path.file_name().unwrap());
+        builder.copy(&path, &dst);
+        linked.push((path, dst));
+    }
+
+    if builder.config.sysroot_depfiles && !builder.config.dry_run {
+        let depfile = linked
+            .iter()
+            .map(|(src, dst)| format!("{}: {}\n", escape_dep_path(dst), escape_dep_path(src)))
+            .collect::<String>();
+        t!(fs::write(stamp.with_extension("d"), depfile));
+    }
+    linked
+}
+
+/// Returns `true` if `stamp` exists and every file it records is still there,
+/// so the artifacts it describes can be reused.
+fn stamp_is_intact(builder: &Builder<'_>, stamp: &Path) -> bool {
+    if builder.config.dry_run {
+        return true;
+    }
+    if !stamp.exists() {
+        return false;
     }
+    let files = builder.read_stamp_file(stamp);
+    !files.is_empty() && files.iter().all(|(path, _)| path.exists())
+}
+
+/// Checks whether `dst` is newer than every input listed for it in the
+/// make-style depfile `dep_file`.
+///
+/// A missing depfile, or one which doesn't mention `dst`, counts as stale so
+/// that `dst` is rebuilt once and the depfile written alongside it.
+fn dep_info_up_to_date(dep_file: &Path, dst: &Path) -> bool {
+    let contents = match fs::read_to_string(dep_file) {
+        Ok(contents) => contents,
+        Err(_) => return false,
+    };
+    if !dst.exists() {
+        return false;
+    }
+    let threshold = mtime(dst);
+    // rustc only escapes the dependencies, the target is written as-is.
+    let dst = dst.display().to_string();
+    let line = match contents
+        .lines()
+        .find(|line| line.starts_with(&dst) && line[dst.len()..].starts_with(':'))
+    {
+        Some(line) => line,
+        None => return false,
+    };
+    // Escaped spaces are temporarily swapped out so the list can be split.
+    let deps = line[dst.len() + 1..].replace("\\ ", "\0");
+    deps.split_whitespace().all(|dep| {
+        let dep = PathBuf::from(dep.replace('\0', " "));
+        dep.exists() && mtime(&dep) <= threshold
+    })
+}
+
+/// Escapes a path for use in a make-style depfile.
+fn escape_dep_path(path: &Path) -> String {
+    path.to_str().unwrap().replace(' ', "\\ ")
+}
+
+/// Runs the Cargo invocation of the `std` or `rustc` step, reporting how long
+/// it took when `build.print-step-timings` or `build.step-timings-json` is set.
+fn run_timed_cargo(
+    builder: &Builder<'_>,
+    step: &str,
+    compiler: Compiler,
+    target: Interned<String>,
+    cargo: Cargo,
+    stamp: &Path,
+    additional_target_deps: Vec<PathBuf>,
+) {
+    let _time = if builder.config.print_step_timings { Some(timeit(builder)) } else { None };
+    let start = Instant::now();
+    run_cargo(builder, cargo, vec![], stamp, additional_target_deps, false);
+    builder.record_step_timing(step, compiler, target, start.elapsed());
 }
 
 pub fn run_cargo(
@@ -1009,3 +1216,6 @@ pub enum CargoMessage<'a> {
         package_id: Cow<'a, str>,
     },
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,270 @@
+use super::*;
+use crate::builder::tests::configure;
+#[cfg(unix)]
+use crate::builder::tests::fake_executable;
+use crate::config::{Config, Target};
+use crate::Build;
+
+fn tmpdir(name: &str) -> PathBuf {
+    let dir = Config::default_opts().out.join("tmp-rustbuild-tests").join("compile").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    t!(fs::create_dir_all(&dir));
+    dir
+}
+
+#[test]
+fn sysroot_missing_std() {
+    let dir = tmpdir("sysroot_missing_std");
+    t!(fs::write(dir.join("libcore-0123abcd.rlib"), ""));
+    t!(fs::write(dir.join("liballoc-0123abcd.rlib"), ""));
+    // Only rlibs count, a stray dylib doesn't make up for a missing libstd.
+    t!(fs::write(dir.join("libstd-0123abcd.so"), ""));
+
+    assert_eq!(missing_sysroot_libs(&dir, &["core", "alloc", "std"]), ["libstd"]);
+    assert!(missing_sysroot_libs(&dir, &["core", "alloc"]).is_empty());
+}
+
+#[test]
+fn version_suffix() {
+    let build = Build::new(configure(&[], &[]));
+    assert_eq!(rustc_version(&Builder::new(&build)), build.rust_version());
+
+    let mut config = configure(&[], &[]);
+    config.rust_version_suffix = Some(" (gentoo)".to_string());
+    let build = Build::new(config);
+    let version = rustc_version(&Builder::new(&build));
+    assert_eq!(version, format!("{} (gentoo)", build.rust_version()));
+}
+
+#[test]
+fn commit_overrides() {
+    // Whether or not this is a git checkout, the overrides win.
+    let mut config = configure(&[], &[]);
+    config.rust_commit_hash = Some("0123456789abcdef0123456789abcdef01234567".to_string());
+    config.rust_commit_date = Some("2020-01-01".to_string());
+    let build = Build::new(config);
+
+    assert_eq!(build.rust_sha(), Some("0123456789abcdef0123456789abcdef01234567"));
+    assert_eq!(build.rust_commit_date(), Some("2020-01-01".to_string()));
+    assert_eq!(build.rust_sha_short(), Some("012345678"));
+    assert_eq!(build.rust_version(), format!("{} (012345678 2020-01-01)", build.rust_release()));
+}
+
+#[test]
+fn sysroot_depfile() {
+    let mut config = configure(&[], &[]);
+    config.sysroot_depfiles = true;
+    let mut build = Build::new(config);
+    // Sanity checks are done, from here on actually copy files around.
+    build.config.dry_run = false;
+    let builder = Builder::new(&build);
+
+    let dir = build.out.join("stage0-std");
+    let core = dir.join("libcore-0123abcd.rlib");
+    let proc_macro = dir.join("libserde_derive-0123abcd.so");
+    t!(fs::create_dir_all(&dir));
+    t!(fs::write(&core, ""));
+    t!(fs::write(&proc_macro, ""));
+
+    // The format written by `run_cargo`: host or target marker, then the path.
+    let stamp = dir.join(".libstd.stamp");
+    let mut contents = Vec::new();
+    for (kind, path) in &[("t", &core), ("h", &proc_macro)] {
+        contents.extend(kind.as_bytes());
+        contents.extend(path.to_str().unwrap().as_bytes());
+        contents.push(0);
+    }
+    t!(fs::write(&stamp, contents));
+
+    let libdir = build.out.join("sysroot/lib");
+    let hostdir = build.out.join("sysroot/host");
+    add_to_sysroot(&builder, &libdir, &hostdir, &stamp);
+
+    let depfile = t!(fs::read_to_string(dir.join(".libstd.d")));
+    assert_eq!(
+        depfile,
+        format!(
+            "{}: {}\n{}: {}\n",
+            libdir.join("libcore-0123abcd.rlib").display(),
+            core.display(),
+            hostdir.join("libserde_derive-0123abcd.so").display(),
+            proc_macro.display(),
+        )
+    );
+}
+
+#[test]
+fn dep_path_escaping() {
+    assert_eq!(escape_dep_path(Path::new("/build dir/libcore.rlib")), "/build\\ dir/libcore.rlib");
+}
+
+#[cfg(unix)]
+#[test]
+fn step_timings_json() {
+    let mut config = configure(&[], &[]);
+    config.step_timings_json = true;
+    let mut build = Build::new(config);
+    build.config.dry_run = false;
+    // A Cargo which builds nothing, all that is left is to time it.
+    let cargo = build.out.join("cargo");
+    fake_executable(&cargo, "#!/bin/sh\nexit 0\n");
+    build.initial_cargo = cargo;
+    let builder = Builder::new(&build);
+
+    let compiler = Compiler { stage: 0, host: build.build };
+    let target = build.build;
+    let steps = [
+        ("std", Mode::Std, libstd_stamp(&builder, compiler, target)),
+        ("rustc", Mode::Rustc, librustc_stamp(&builder, compiler, target)),
+    ];
+    for (step, mode, stamp) in &steps {
+        t!(fs::create_dir_all(stamp.parent().unwrap().join("deps")));
+        let cargo = builder.cargo(compiler, *mode, target, "build");
+        run_timed_cargo(&builder, step, compiler, target, cargo, stamp, vec![]);
+    }
+
+    let timings = build.step_timings.borrow();
+    let recorded =
+        timings.iter().map(|t| (&t.step[..], t.stage, &t.target[..])).collect::<Vec<_>>();
+    assert_eq!(recorded, [("std", 0, "A"), ("rustc", 0, "A")]);
+}
+
+#[test]
+fn sysroot_manifest() {
+    let mut config = configure(&[], &[]);
+    config.sysroot_manifest = true;
+    let mut build = Build::new(config);
+    build.config.dry_run = false;
+
+    let compiler = Compiler { stage: 1, host: build.build };
+    let produced = build.out.join("stage1-std/libcore-0123abcd.rlib");
+    let installed = build.out.join("sysroot/lib/libcore-0123abcd.rlib");
+    let linked = vec![(produced.clone(), installed.clone())];
+    build.record_sysroot_artifacts(compiler, build.build, Mode::Std, linked);
+
+    let manifest: serde_json::Value = t!(serde_json::from_str(&build.sysroot_manifest()));
+    assert_eq!(
+        manifest,
+        serde_json::json!([{
+            "stage": 1,
+            "target": "A",
+            "mode": "std",
+            "produced": [produced],
+            "installed": [installed],
+        }])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn stage0_target_list() {
+    let mut build = Build::new(configure(&[], &[]));
+    build.config.dry_run = false;
+    // Only answers `--print target-list`, which is all that is asked of it.
+    let rustc = build.out.join("rustc");
+    fake_executable(&rustc, "#!/bin/sh\nprintf 'A\\nx86_64-gentoo-linux-musl\\n'\n");
+    build.initial_rustc = rustc;
+    let builder = Builder::new(&build);
+
+    let check = |target| check_stage0_knows_target(&builder, INTERNER.intern_str(target));
+    assert_eq!(check("A"), Ok(()));
+    assert_eq!(check("x86_64-gentoo-linux-musl"), Ok(()));
+    assert!(check("x86_64").is_err());
+    assert_eq!(check("x86_64-foxkit-linux-musl.json"), Ok(()));
+
+    let err = check("x86_64-foxkit-linux-musl").unwrap_err();
+    assert!(err.contains("does not support the target `x86_64-foxkit-linux-musl`"), "{}", err);
+    assert!(err.contains("`--target path/to/x86_64-foxkit-linux-musl.json`"), "{}", err);
+}
+
+#[test]
+fn dep_info_freshness() {
+    let dir = tmpdir("dep_info_freshness").join("build dir");
+    t!(fs::create_dir_all(&dir));
+    let src = dir.join("rs begin.rs");
+    let dst = dir.join("rsbegin.o");
+    let dep_file = dir.join("rsbegin.d");
+    t!(fs::write(&src, ""));
+    t!(fs::write(&dst, ""));
+    t!(filetime::set_file_mtime(&src, FileTime::from_unix_time(1_000, 0)));
+    t!(filetime::set_file_mtime(&dst, FileTime::from_unix_time(2_000, 0)));
+
+    // Without a depfile the object is rebuilt once to produce one.
+    assert!(!dep_info_up_to_date(&dep_file, &dst));
+
+    // Written like rustc does, which only escapes the dependencies.
+    let deps = format!("{}: {}\n", dst.display(), escape_dep_path(&src));
+    t!(fs::write(&dep_file, &deps));
+    assert!(dep_info_up_to_date(&dep_file, &dst));
+
+    t!(filetime::set_file_mtime(&src, FileTime::from_unix_time(3_000, 0)));
+    assert!(!dep_info_up_to_date(&dep_file, &dst));
+    t!(filetime::set_file_mtime(&src, FileTime::from_unix_time(1_000, 0)));
+
+    // The depfile lists inputs of other outputs, but nothing for this one.
+    let other = format!("{}: {}\n", dir.join("rsend.o").display(), escape_dep_path(&src));
+    t!(fs::write(&dep_file, other));
+    assert!(!dep_info_up_to_date(&dep_file, &dst));
+
+    // An input which has gone away.
+    t!(fs::write(&dep_file, &deps));
+    t!(fs::remove_file(&src));
+    assert!(!dep_info_up_to_date(&dep_file, &dst));
+}
+
+#[test]
+fn minimal_sysroot() {
+    let mut config = configure(&[], &["B"]);
+    let b = INTERNER.intern_str("B");
+    config.target_config.insert(b, Target { minimal_sysroot: true, ..Default::default() });
+    let mut build = Build::new(config);
+    build.config.dry_run = false;
+    let builder = Builder::new(&build);
+
+    let libdir = build.out.join("sysroot-lib");
+    t!(fs::create_dir_all(&libdir));
+    for lib in &["core", "alloc", "std"] {
+        t!(fs::write(libdir.join(format!("lib{}-0123abcd.rlib", lib)), ""));
+    }
+
+    assert_eq!(verify_sysroot(&builder, &libdir, b), Ok(()));
+    assert_eq!(verify_sysroot(&builder, &libdir, build.build), Err(vec!["libtest".to_string()]));
+
+    t!(fs::remove_file(libdir.join("libcore-0123abcd.rlib")));
+    assert_eq!(verify_sysroot(&builder, &libdir, b), Err(vec!["libcore".to_string()]));
+}
+
+#[test]
+#[should_panic(expected = "target.A.minimal-sysroot can't be used for a host")]
+fn minimal_sysroot_host() {
+    let mut config = configure(&[], &[]);
+    let a = config.build;
+    config.target_config.insert(a, Target { minimal_sysroot: true, ..Default::default() });
+    Build::new(config);
+}
+
+#[test]
+fn kept_stamp_is_intact() {
+    let mut build = Build::new(configure(&[], &[]));
+    build.config.dry_run = false;
+    let builder = Builder::new(&build);
+
+    let dir = build.out.join("stage1-std");
+    let stamp = dir.join(".libstd.stamp");
+    let libstd = dir.join("libstd-0123abcd.rlib");
+    t!(fs::create_dir_all(&dir));
+    t!(fs::write(&libstd, ""));
+    assert!(!stamp_is_intact(&builder, &stamp));
+
+    t!(fs::write(&stamp, ""));
+    assert!(!stamp_is_intact(&builder, &stamp));
+
+    let mut contents = b"t".to_vec();
+    contents.extend(libstd.to_str().unwrap().as_bytes());
+    contents.push(0);
+    t!(fs::write(&stamp, contents));
+    assert!(stamp_is_intact(&builder, &stamp));
+
+    t!(fs::remove_file(&libstd));
+    assert!(!stamp_is_intact(&builder, &stamp));
+}
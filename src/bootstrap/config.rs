@@ -56,6 +56,7 @@ pub struct Config {
     pub on_fail: Option<String>,
     pub stage: Option<u32>,
     pub keep_stage: Vec<u32>,
+    pub keep_stage_std: Vec<u32>,
     pub src: PathBuf,
     pub jobs: Option<u32>,
     pub cmd: Subcommand,
@@ -104,6 +105,9 @@ pub struct Config {
     pub rust_rpath: bool,
     pub rustc_parallel: bool,
     pub rustc_default_linker: Option<String>,
+    pub rust_version_suffix: Option<String>,
+    pub rust_commit_date: Option<String>,
+    pub rust_commit_hash: Option<String>,
     pub rust_optimize_tests: bool,
     pub rust_dist_src: bool,
     pub rust_codegen_backends: Vec<Interned<String>>,
@@ -132,6 +136,9 @@ pub struct Config {
     pub verbose_tests: bool,
     pub save_toolstates: Option<PathBuf>,
     pub print_step_timings: bool,
+    pub sysroot_depfiles: bool,
+    pub sysroot_manifest: bool,
+    pub step_timings_json: bool,
     pub missing_tools: bool,
 
     // Fallback musl-root for all targets
@@ -171,10 +178,15 @@ pub struct Target {
     pub linker: Option<PathBuf>,
     pub ndk: Option<PathBuf>,
     pub crt_static: Option<bool>,
+    pub dynamic_linker: Option<PathBuf>,
+    pub no_as_needed: bool,
+    pub llvm_libunwind: Option<bool>,
+    pub target_cpu: Option<String>,
     pub musl_root: Option<PathBuf>,
     pub wasi_root: Option<PathBuf>,
     pub qemu_rootfs: Option<PathBuf>,
     pub no_std: bool,
+    pub minimal_sysroot: bool,
 }
 
 impl Target {
@@ -234,6 +246,9 @@ struct Build {
     configure_args: Option<Vec<String>>,
     local_rebuild: Option<bool>,
     print_step_timings: Option<bool>,
+    sysroot_depfiles: Option<bool>,
+    sysroot_manifest: Option<bool>,
+    step_timings_json: Option<bool>,
 }
 
 /// TOML representation of various global install decisions.
@@ -322,6 +337,9 @@ struct Rust {
     parallel_compiler: Option<bool>,
     default_linker: Option<String>,
     channel: Option<String>,
+    version_suffix: Option<String>,
+    commit_date: Option<String>,
+    commit_hash: Option<String>,
     musl_root: Option<String>,
     rpath: Option<bool>,
     verbose_tests: Option<bool>,
@@ -359,10 +377,15 @@ struct TomlTarget {
     llvm_filecheck: Option<String>,
     android_ndk: Option<String>,
     crt_static: Option<bool>,
+    dynamic_linker: Option<String>,
+    no_as_needed: Option<bool>,
+    llvm_libunwind: Option<bool>,
+    target_cpu: Option<String>,
     musl_root: Option<String>,
     wasi_root: Option<String>,
     qemu_rootfs: Option<String>,
     no_std: Option<bool>,
+    minimal_sysroot: Option<bool>,
 }
 
 impl Config {
@@ -422,6 +445,7 @@ impl Config {
         config.incremental = flags.incremental;
         config.dry_run = flags.dry_run;
         config.keep_stage = flags.keep_stage;
+        config.keep_stage_std = flags.keep_stage_std;
         config.bindir = "bin".into(); // default
         if let Some(value) = flags.deny_warnings {
             config.deny_warnings = value;
@@ -494,6 +518,9 @@ impl Config {
         set(&mut config.configure_args, build.configure_args);
         set(&mut config.local_rebuild, build.local_rebuild);
         set(&mut config.print_step_timings, build.print_step_timings);
+        set(&mut config.sysroot_depfiles, build.sysroot_depfiles);
+        set(&mut config.sysroot_manifest, build.sysroot_manifest);
+        set(&mut config.step_timings_json, build.step_timings_json);
         config.verbose = cmp::max(config.verbose, flags.verbose);
 
         if let Some(ref install) = toml.install {
@@ -585,6 +612,9 @@ impl Config {
             set(&mut config.llvm_tools_enabled, rust.llvm_tools);
             config.rustc_parallel = rust.parallel_compiler.unwrap_or(false);
             config.rustc_default_linker = rust.default_linker.clone();
+            config.rust_version_suffix = rust.version_suffix.clone();
+            config.rust_commit_date = rust.commit_date.clone();
+            config.rust_commit_hash = rust.commit_hash.clone();
             config.musl_root = rust.musl_root.clone().map(PathBuf::from);
             config.save_toolstates = rust.save_toolstates.clone().map(PathBuf::from);
             set(&mut config.deny_warnings, flags.deny_warnings.or(rust.deny_warnings));
@@ -619,12 +649,21 @@ impl Config {
                 if let Some(s) = cfg.no_std {
                     target.no_std = s;
                 }
+                if let Some(s) = cfg.minimal_sysroot {
+                    target.minimal_sysroot = s;
+                }
+                if let Some(s) = cfg.no_as_needed {
+                    target.no_as_needed = s;
+                }
                 target.cc = cfg.cc.clone().map(PathBuf::from);
                 target.cxx = cfg.cxx.clone().map(PathBuf::from);
                 target.ar = cfg.ar.clone().map(PathBuf::from);
                 target.ranlib = cfg.ranlib.clone().map(PathBuf::from);
                 target.linker = cfg.linker.clone().map(PathBuf::from);
                 target.crt_static = cfg.crt_static;
+                target.llvm_libunwind = cfg.llvm_libunwind;
+                target.target_cpu = cfg.target_cpu.clone();
+                target.dynamic_linker = cfg.dynamic_linker.clone().map(PathBuf::from);
                 target.musl_root = cfg.musl_root.clone().map(PathBuf::from);
                 target.wasi_root = cfg.wasi_root.clone().map(PathBuf::from);
                 target.qemu_rootfs = cfg.qemu_rootfs.clone().map(PathBuf::from);
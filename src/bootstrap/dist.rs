@@ -21,8 +21,8 @@ use crate::cache::{Interned, INTERNER};
 use crate::channel;
 use crate::compile;
 use crate::tool::{self, Tool};
-use crate::util::{exe, is_dylib, timeit};
-use crate::{Compiler, Mode, LLVM_TOOLS};
+use crate::util::{add_lib_path, exe, is_dylib, timeit};
+use crate::{spec_supports_dynamic_musl, Compiler, Mode, LLVM_TOOLS};
 use time::{self, Timespec};
 
 pub fn pkgname(builder: &Builder<'_>, component: &str) -> String {
@@ -2254,6 +2254,55 @@ impl Step for HashSign {
     }
 }
 
+#[derive(Debug, PartialOrd, Ord, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct TargetSpecs;
+
+impl Step for TargetSpecs {
+    type Output = PathBuf;
+    const ONLY_HOSTS: bool = true;
+
+    fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
+        run.path("target-specs")
+    }
+
+    fn make_run(run: RunConfig<'_>) {
+        run.builder.ensure(TargetSpecs);
+    }
+
+    /// Writes the JSON specification of every built-in musl target linking
+    /// dynamically by default, i.e. the vendor ones, to `dist/target-specs`,
+    /// as a starting point for custom targets.
+    fn run(self, builder: &Builder<'_>) -> PathBuf {
+        let compiler = builder.compiler(builder.top_stage, builder.config.build);
+        let dst = distdir(builder).join("target-specs");
+        if builder.config.dry_run {
+            return dst;
+        }
+        t!(fs::create_dir_all(&dst));
+
+        let rustc = |args: &[&str]| {
+            let mut cmd = Command::new(builder.rustc(compiler));
+            add_lib_path(vec![builder.rustc_libdir(compiler)], &mut cmd);
+            // `--print target-spec-json` is unstable.
+            cmd.env("RUSTC_BOOTSTRAP", "1").args(args);
+            output(&mut cmd)
+        };
+
+        let targets = rustc(&["--print", "target-list"]);
+        for target in targets.lines().map(|t| t.trim()) {
+            let json =
+                rustc(&["-Zunstable-options", "--print", "target-spec-json", "--target", target]);
+            let spec: serde_json::Value = t!(serde_json::from_str(&json));
+            if !spec_supports_dynamic_musl(&spec) {
+                continue;
+            }
+            builder.info(&format!("Dumping target specification for {}", target));
+            t!(fs::write(dst.join(format!("{}.json", target)), json));
+        }
+        dst
+    }
+}
+
 // Maybe add libLLVM.so to the lib-dir. It will only have been built if
 // LLVM tools are linked dynamically.
 //
@@ -287,8 +287,8 @@ impl Step for Standalone {
         if !builder.config.dry_run && !up_to_date(&version_input, &version_info) {
             let info = t!(fs::read_to_string(&version_input))
                 .replace("VERSION", &builder.rust_release())
-                .replace("SHORT_HASH", builder.rust_info.sha_short().unwrap_or(""))
-                .replace("STAMP", builder.rust_info.sha().unwrap_or(""));
+                .replace("SHORT_HASH", builder.rust_sha_short().unwrap_or(""))
+                .replace("STAMP", builder.rust_sha().unwrap_or(""));
             t!(fs::write(&version_info, &info));
         }
 
@@ -114,6 +114,7 @@ use std::path::{Path, PathBuf};
 use std::process::{self, Command};
 use std::slice;
 use std::str;
+use std::time::Duration;
 
 #[cfg(unix)]
 use std::os::unix::fs::symlink as symlink_file;
@@ -122,6 +123,7 @@ use std::os::windows::fs::symlink_file;
 
 use build_helper::{mtime, output, run, run_suppressed, t, try_run, try_run_suppressed};
 use filetime::FileTime;
+use serde::Serialize;
 
 use crate::util::{exe, libdir, CiEnv};
 
@@ -178,6 +180,22 @@ const LLVM_TOOLS: &[&str] = &[
     "llvm-ar", // used for creating and modifying archive files
 ];
 
+/// Returns `true` if `spec`, a target specification in the JSON format used by
+/// `--print target-spec-json`, is for a musl target linking dynamically by
+/// default. This mirrors `Target::supports_dynamic_musl` in `rustc_target`.
+fn spec_supports_dynamic_musl(spec: &serde_json::Value) -> bool {
+    spec["env"].as_str() == Some("musl") && !spec["crt-static-default"].as_bool().unwrap_or(false)
+}
+
+/// Reads the specification of `target` if it is a custom target given as the
+/// path to a JSON file.
+fn custom_target_spec(target: &str) -> Option<serde_json::Value> {
+    if !target.ends_with(".json") {
+        return None;
+    }
+    serde_json::from_str(&fs::read_to_string(target).ok()?).ok()
+}
+
 /// A structure representing a Rust compiler.
 ///
 /// Each compiler has a `stage` that it is associated with and a `host` that
@@ -257,6 +275,32 @@ pub struct Build {
     prerelease_version: Cell<Option<u32>>,
     tool_artifacts:
         RefCell<HashMap<Interned<String>, HashMap<String, (&'static str, PathBuf, Vec<String>)>>>,
+    sysroot_artifacts: RefCell<Vec<SysrootArtifacts>>,
+    step_timings: RefCell<Vec<StepTiming>>,
+}
+
+/// The files linked into a sysroot by one of the `*Link` steps, as written out
+/// when `build.sysroot-manifest` is enabled.
+#[derive(Serialize)]
+struct SysrootArtifacts {
+    stage: u32,
+    target: String,
+    mode: String,
+    /// Artifacts produced by Cargo, as listed in the stamp file.
+    produced: Vec<PathBuf>,
+    /// Where each of the `produced` artifacts was linked into the sysroot.
+    installed: Vec<PathBuf>,
+}
+
+/// How long the Cargo invocation of a std or rustc build took, as written out
+/// when `build.step-timings-json` is enabled.
+#[derive(Serialize)]
+struct StepTiming {
+    step: String,
+    stage: u32,
+    host: String,
+    target: String,
+    seconds: f64,
 }
 
 #[derive(Debug)]
@@ -385,6 +429,8 @@ impl Build {
             delayed_failures: RefCell::new(Vec::new()),
             prerelease_version: Cell::new(None),
             tool_artifacts: Default::default(),
+            sysroot_artifacts: RefCell::new(Vec::new()),
+            step_timings: RefCell::new(Vec::new()),
         };
 
         build.verbose("finding compilers");
@@ -456,6 +502,16 @@ impl Build {
             builder.execute_cli();
         }
 
+        if self.config.sysroot_manifest && !self.config.dry_run {
+            t!(fs::write(self.out.join("sysroot-artifacts.json"), self.sysroot_manifest()));
+        }
+
+        if self.config.step_timings_json && !self.config.dry_run {
+            let timings = self.step_timings.borrow();
+            let timings = t!(serde_json::to_string_pretty(&*timings));
+            t!(fs::write(self.out.join("step-timings.json"), timings));
+        }
+
         // Check for postponed failures from `test --no-fail-fast`.
         let failures = self.delayed_failures.borrow();
         if failures.len() > 0 {
@@ -486,11 +542,17 @@ impl Build {
     }
 
     /// Gets the space-separated set of activated features for the standard
-    /// library.
-    fn std_features(&self) -> String {
+    /// library when built for `target`.
+    fn std_features(&self, target: Interned<String>) -> String {
         let mut features = "panic-unwind".to_string();
 
-        if self.config.llvm_libunwind {
+        let llvm_libunwind = self
+            .config
+            .target_config
+            .get(&target)
+            .and_then(|t| t.llvm_libunwind)
+            .unwrap_or(self.config.llvm_libunwind);
+        if llvm_libunwind {
             features.push_str(" llvm-libunwind");
         }
         if self.config.backtrace {
@@ -836,14 +898,50 @@ impl Build {
     }
 
     /// Returns if this target should statically link the C runtime, if specified
+    ///
+    /// Custom musl targets whose specification defaults to the system's dynamic
+    /// musl (`crt-static-default = false`) are made explicit here, unless
+    /// configured otherwise.
     fn crt_static(&self, target: Interned<String>) -> Option<bool> {
         if target.contains("pc-windows-msvc") {
             Some(true)
+        } else if let Some(x) = self.config.target_config.get(&target).and_then(|t| t.crt_static) {
+            Some(x)
+        } else if custom_target_spec(&target).map_or(false, |s| spec_supports_dynamic_musl(&s)) {
+            Some(false)
         } else {
-            self.config.target_config.get(&target).and_then(|t| t.crt_static)
+            None
         }
     }
 
+    /// Returns `true` if `target` is a musl target configured to link against
+    /// the system's dynamic musl rather than a static copy in the sysroot.
+    ///
+    /// This is the bootstrap counterpart of `Target::supports_dynamic_musl` in
+    /// `rustc_target`, based on the `crt-static` setting of the target config
+    /// and, for custom targets, their specification.
+    fn supports_dynamic_musl(&self, target: Interned<String>) -> bool {
+        target.contains("musl") && self.crt_static(target) == Some(false)
+    }
+
+    /// Returns the dynamic linker that executables for `target` should request,
+    /// if overridden in the target's configuration.
+    fn dynamic_linker(&self, target: Interned<String>) -> Option<&Path> {
+        self.config.target_config.get(&target).and_then(|t| t.dynamic_linker.as_ref()).map(|p| &**p)
+    }
+
+    /// Returns `true` if the `--as-needed` linker flag from the target spec
+    /// should be overridden with `--no-as-needed` for `target`.
+    fn no_as_needed(&self, target: Interned<String>) -> bool {
+        self.config.target_config.get(&target).map_or(false, |t| t.no_as_needed)
+    }
+
+    /// Returns the CPU the standard library and compiler should be optimized
+    /// for on `target`, if overridden.
+    fn target_cpu(&self, target: Interned<String>) -> Option<&str> {
+        self.config.target_config.get(&target).and_then(|t| t.target_cpu.as_ref()).map(|s| &**s)
+    }
+
     /// Returns the "musl root" for this `target`, if defined
     fn musl_root(&self, target: Interned<String>) -> Option<&Path> {
         self.config
@@ -864,6 +962,12 @@ impl Build {
         self.config.target_config.get(&target).map(|t| t.no_std)
     }
 
+    /// Returns `true` if only the standard library, and not libtest or
+    /// libproc_macro, should be built for `target`.
+    fn minimal_sysroot(&self, target: Interned<String>) -> bool {
+        self.config.target_config.get(&target).map_or(false, |t| t.minimal_sysroot)
+    }
+
     /// Returns `true` if the target will be tested using the `remote-test-client`
     /// and `remote-test-server` binaries.
     fn remote_tested(&self, target: Interned<String>) -> bool {
@@ -1046,12 +1150,31 @@ impl Build {
     /// Note that this is a descriptive string which includes the commit date,
     /// sha, version, etc.
     fn rust_version(&self) -> String {
-        self.rust_info.version(self, channel::CFG_RELEASE_NUM)
+        let mut version = self.rust_release();
+        if let (Some(sha), Some(date)) = (self.rust_sha_short(), self.rust_commit_date()) {
+            version.push_str(&format!(" ({} {})", sha, date));
+        }
+        version
     }
 
-    /// Returns the full commit hash.
+    /// Returns the full commit hash, from `rust.commit-hash` or git.
     fn rust_sha(&self) -> Option<&str> {
-        self.rust_info.sha()
+        self.config.rust_commit_hash.as_deref().or_else(|| self.rust_info.sha())
+    }
+
+    /// Returns the commit hash shortened like `git rev-parse --short=9` does.
+    fn rust_sha_short(&self) -> Option<&str> {
+        self.rust_sha().map(|sha| sha.get(..9).unwrap_or(sha))
+    }
+
+    /// Returns the commit date, from `rust.commit-date`, git, or failing that
+    /// `SOURCE_DATE_EPOCH`.
+    fn rust_commit_date(&self) -> Option<String> {
+        self.config
+            .rust_commit_date
+            .clone()
+            .or_else(|| self.rust_info.commit_date().map(str::to_string))
+            .or_else(|| env::var("SOURCE_DATE_EPOCH").ok().map(|epoch| util::epoch_date(&epoch)))
     }
 
     /// Returns the `a.b.c` version that the given package is at.
@@ -1096,6 +1219,55 @@ impl Build {
         ret
     }
 
+    /// Records the files linked into the sysroot of `compiler` for `target`,
+    /// for the manifest written when `build.sysroot-manifest` is enabled.
+    fn record_sysroot_artifacts(
+        &self,
+        compiler: Compiler,
+        target: Interned<String>,
+        mode: Mode,
+        linked: Vec<(PathBuf, PathBuf)>,
+    ) {
+        if !self.config.sysroot_manifest || self.config.dry_run {
+            return;
+        }
+        let (produced, installed) = linked.into_iter().unzip();
+        self.sysroot_artifacts.borrow_mut().push(SysrootArtifacts {
+            stage: compiler.stage,
+            target: target.to_string(),
+            mode: format!("{:?}", mode).to_lowercase(),
+            produced,
+            installed,
+        });
+    }
+
+    /// Records how long building `step` with `compiler` for `target` took, for
+    /// the file written when `build.step-timings-json` is enabled.
+    fn record_step_timing(
+        &self,
+        step: &str,
+        compiler: Compiler,
+        target: Interned<String>,
+        time: Duration,
+    ) {
+        if !self.config.step_timings_json || self.config.dry_run {
+            return;
+        }
+        self.step_timings.borrow_mut().push(StepTiming {
+            step: step.to_string(),
+            stage: compiler.stage,
+            host: compiler.host.to_string(),
+            target: target.to_string(),
+            seconds: time.as_secs() as f64 + f64::from(time.subsec_millis()) / 1000.0,
+        });
+    }
+
+    /// Renders the recorded sysroot artifacts as the JSON written to
+    /// `sysroot-artifacts.json`.
+    fn sysroot_manifest(&self) -> String {
+        t!(serde_json::to_string_pretty(&*self.sysroot_artifacts.borrow()))
+    }
+
     fn read_stamp_file(&self, stamp: &Path) -> Vec<(PathBuf, bool)> {
         if self.config.dry_run {
             return Vec::new();
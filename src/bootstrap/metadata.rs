@@ -36,7 +36,7 @@ struct ResolveNode {
 
 pub fn build(build: &mut Build) {
     let mut resolves = Vec::new();
-    build_krate(&build.std_features(), build, &mut resolves, "src/libstd");
+    build_krate(&build.std_features(build.build), build, &mut resolves, "src/libstd");
     build_krate("", build, &mut resolves, "src/libtest");
     build_krate(&build.rustc_features(), build, &mut resolves, "src/rustc");
 
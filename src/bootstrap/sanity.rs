@@ -179,6 +179,12 @@ pub fn check(build: &mut Build) {
         if !build.config.dry_run {
             cmd_finder.must_have(build.cxx(*host).unwrap());
         }
+
+        // The compiler and tools built for a host need libtest and
+        // libproc_macro in its sysroot.
+        if build.minimal_sysroot(*host) {
+            panic!("target.{}.minimal-sysroot can't be used for a host", host);
+        }
     }
 
     // Externally configured LLVM requires FileCheck to exist
@@ -201,8 +207,9 @@ pub fn check(build: &mut Build) {
             }
         }
 
-        // Make sure musl-root is valid
-        if target.contains("musl") {
+        // Make sure musl-root is valid, unless the target links against the
+        // system's dynamic musl and doesn't need anything from it
+        if target.contains("musl") && !build.supports_dynamic_musl(*target) {
             // If this is a native target (host is also musl) and no musl-root is given,
             // fall back to the system toolchain in /usr before giving up
             if build.musl_root(*target).is_none() && build.config.build == *target {
@@ -251,6 +258,10 @@ $ pacman -R cmake && pacman -S mingw-w64-x86_64-cmake
         cmd_finder.must_have(s);
     }
 
+    if let Err(e) = check_channel(&build.config.channel) {
+        panic!("{}", e);
+    }
+
     if build.config.channel == "stable" {
         let stage0 = t!(fs::read_to_string(build.src.join("src/stage0.txt")));
         if stage0.contains("\ndev:") {
@@ -261,3 +272,19 @@ $ pacman -R cmake && pacman -S mingw-w64-x86_64-cmake
         }
     }
 }
+
+/// `CFG_RELEASE` is derived from the channel, with anything unknown ending up
+/// as a `-dev` release which then doesn't match `CFG_RELEASE_CHANNEL`.
+fn check_channel(channel: &str) -> Result<(), String> {
+    match channel {
+        "stable" | "beta" | "nightly" | "dev" => Ok(()),
+        channel => Err(format!(
+            "unknown release channel `{}`, expected one of \
+                `stable`, `beta`, `nightly` or `dev`",
+            channel
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests;
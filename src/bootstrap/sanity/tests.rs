@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn known_channels() {
+    for channel in &["stable", "beta", "nightly", "dev"] {
+        assert_eq!(check_channel(channel), Ok(()));
+    }
+}
+
+#[test]
+fn unknown_channel() {
+    let err = check_channel("gentoo").unwrap_err();
+    assert!(err.contains("unknown release channel `gentoo`"), "{}", err);
+    assert!(check_channel("").is_err());
+    assert!(check_channel("Stable").is_err());
+}
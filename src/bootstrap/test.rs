@@ -1940,6 +1940,91 @@ impl Step for Distcheck {
     }
 }
 
+/// Links an empty program for a musl target linking dynamically, such as the
+/// vendor ones, making sure the linker arguments from its target spec work
+/// with the configured linker.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VendorLink {
+    pub compiler: Compiler,
+    pub target: Interned<String>,
+}
+
+impl Step for VendorLink {
+    type Output = ();
+    const DEFAULT: bool = true;
+
+    fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
+        run.path("vendor-link")
+    }
+
+    fn make_run(run: RunConfig<'_>) {
+        if !run.builder.supports_dynamic_musl(run.target) {
+            return;
+        }
+        if vendor_linker(run.builder, run.target).is_none() {
+            run.builder.info(&format!(
+                "Skipping link test for {}: no target.{}.linker configured",
+                run.target, run.target
+            ));
+            return;
+        }
+        let compiler = run.builder.compiler(run.builder.top_stage, run.host);
+        run.builder.ensure(VendorLink { compiler, target: run.target });
+    }
+
+    fn run(self, builder: &Builder<'_>) {
+        let compiler = self.compiler;
+        let target = self.target;
+        if !builder.supports_dynamic_musl(target) {
+            return;
+        }
+        let linker = match vendor_linker(builder, target) {
+            Some(linker) => linker,
+            None => return,
+        };
+
+        builder.ensure(compile::Std { compiler, target });
+
+        let dir = builder.out.join(&*target).join("vendor-link");
+        builder.create_dir(&dir);
+        let src = dir.join("main.rs");
+        builder.create(&src, "fn main() {}\n");
+
+        let mut cmd = Command::new(builder.rustc(compiler));
+        util::add_lib_path(vec![builder.rustc_libdir(compiler)], &mut cmd);
+        cmd.args(vendor_link_args(builder, target, &linker, &src, &dir.join("main")));
+
+        builder.info(&format!("Linking a test program for {} with {}", target, linker.display()));
+        try_run(builder, &mut cmd);
+    }
+}
+
+/// Returns the linker configured for `target`. Only an explicitly configured
+/// cross-linker is worth testing, the fallback to the target's C compiler
+/// usually can't link for it.
+fn vendor_linker(builder: &Builder<'_>, target: Interned<String>) -> Option<PathBuf> {
+    builder.config.target_config.get(&target).and_then(|t| t.linker.clone())
+}
+
+/// Returns the arguments for rustc to link `src` into `out` for `target` using
+/// `linker`.
+pub(crate) fn vendor_link_args(
+    builder: &Builder<'_>,
+    target: Interned<String>,
+    linker: &Path,
+    src: &Path,
+    out: &Path,
+) -> Vec<OsString> {
+    let mut linker_arg = OsString::from("-Clinker=");
+    linker_arg.push(linker);
+    let mut args = vec!["--target".into(), (&*target).into(), linker_arg];
+    if let Some(dynamic_linker) = builder.dynamic_linker(target) {
+        args.push(format!("-Clink-arg=-Wl,-dynamic-linker,{}", dynamic_linker.display()).into());
+    }
+    args.extend(vec!["-o".into(), out.into(), src.into()]);
+    args
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Bootstrap;
 
@@ -253,7 +253,7 @@ pub fn prepare_tool_cargo(
     cargo.env("LZMA_API_STATIC", "1");
 
     cargo.env("CFG_RELEASE_CHANNEL", &builder.config.channel);
-    cargo.env("CFG_VERSION", builder.rust_version());
+    cargo.env("CFG_VERSION", compile::rustc_version(builder));
     cargo.env("CFG_RELEASE_NUM", channel::CFG_RELEASE_NUM);
 
     let info = GitInfo::new(builder.config.ignore_git, &dir);
@@ -4,6 +4,7 @@
 //! not a lot of interesting happenings here unfortunately.
 
 use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -12,6 +13,7 @@ use std::str;
 use std::time::Instant;
 
 use build_helper::t;
+use time::{self, Timespec};
 
 use crate::builder::Builder;
 use crate::cache::Interned;
@@ -103,6 +105,13 @@ impl Drop for TimeIt {
     }
 }
 
+/// Formats the `SOURCE_DATE_EPOCH` timestamp `epoch` as a `YYYY-MM-DD` date
+/// in UTC, like the commit dates taken from git.
+pub fn epoch_date(epoch: &str) -> String {
+    let epoch = t!(epoch.parse(), format!("SOURCE_DATE_EPOCH={}", epoch));
+    t!(time::strftime("%Y-%m-%d", &time::at_utc(Timespec::new(epoch, 0))))
+}
+
 /// Symlinks two directories, using junctions on Windows and normal symlinks on
 /// Unix.
 pub fn symlink_dir(config: &Config, src: &Path, dest: &Path) -> io::Result<()> {
@@ -278,3 +287,22 @@ pub fn use_host_linker(target: &Interned<String>) -> bool {
         || target.contains("fortanix")
         || target.contains("fuchsia"))
 }
+
+/// Returns the codegen option setting the dynamic linker to `dynamic_linker`,
+/// if the rustc command line `args` builds an executable.
+///
+/// Shared objects like `libstd.so` must not get a program interpreter, which
+/// is why this can't be passed in `RUSTFLAGS` for all crates alike.
+pub fn dynamic_linker_arg(args: &[OsString], dynamic_linker: &OsStr) -> Option<OsString> {
+    let executable = args.iter().any(|arg| arg == "--test")
+        || args.windows(2).any(|w| w[0] == "--crate-type" && w[1] == "bin");
+    if !executable {
+        return None;
+    }
+    let mut arg = OsString::from("link-arg=-Wl,-dynamic-linker,");
+    arg.push(dynamic_linker);
+    Some(arg)
+}
+
+#[cfg(test)]
+mod tests;
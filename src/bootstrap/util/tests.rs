@@ -0,0 +1,28 @@
+use super::*;
+
+fn args(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn dynamic_linker_only_for_executables() {
+    let ld = OsStr::new("/lib/ld-musl-i386.so.1");
+    let expected = Some(OsString::from("link-arg=-Wl,-dynamic-linker,/lib/ld-musl-i386.so.1"));
+
+    let bin = args(&["--crate-name", "rustc_binary", "--crate-type", "bin"]);
+    assert_eq!(dynamic_linker_arg(&bin, ld), expected);
+    let test = args(&["--crate-name", "std", "--test"]);
+    assert_eq!(dynamic_linker_arg(&test, ld), expected);
+
+    let dylib = args(&["--crate-name", "std", "--crate-type", "dylib", "--crate-type", "rlib"]);
+    assert_eq!(dynamic_linker_arg(&dylib, ld), None);
+    let proc_macro = args(&["--crate-name", "serde_derive", "--crate-type", "proc-macro"]);
+    assert_eq!(dynamic_linker_arg(&proc_macro, ld), None);
+}
+
+#[test]
+fn source_date_epoch() {
+    assert_eq!(epoch_date("0"), "1970-01-01");
+    assert_eq!(epoch_date("1577836799"), "2019-12-31");
+    assert_eq!(epoch_date("1577836800"), "2020-01-01");
+}
@@ -74,6 +74,9 @@ mod windows_msvc_base;
 mod windows_uwp_base;
 mod windows_uwp_msvc_base;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum LinkerFlavor {
     Em,
@@ -291,6 +294,7 @@ macro_rules! supported_targets {
                         // run-time that the parser works correctly
                         t = Target::from_json(t.to_json())
                             .map_err(LoadTargetError::Other)?;
+                        t.validate().map_err(LoadTargetError::Other)?;
                         debug!("got builtin target: {:?}", t);
                         Ok(t)
                     },
@@ -379,6 +383,10 @@ supported_targets! {
     ("mips64el-unknown-linux-muslabi64", mips64el_unknown_linux_muslabi64),
     ("hexagon-unknown-linux-musl", hexagon_unknown_linux_musl),
 
+    ("i586-gentoo-linux-musl", i586_gentoo_linux_musl),
+
+    ("powerpc64le-foxkit-linux-musl", powerpc64le_foxkit_linux_musl),
+
     ("mips-unknown-linux-uclibc", mips_unknown_linux_uclibc),
     ("mipsel-unknown-linux-uclibc", mipsel_unknown_linux_uclibc),
 
@@ -950,6 +958,37 @@ impl Target {
         abi.generic() || !self.options.abi_blacklist.contains(&abi)
     }
 
+    /// Returns `true` if this is a musl target which by default links against
+    /// the system's dynamic musl instead of a statically linked copy.
+    pub fn supports_dynamic_musl(&self) -> bool {
+        self.target_env == "musl" && !self.options.crt_static_default
+    }
+
+    /// Checks that the fields of this target are consistent with each other,
+    /// returning a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        let components = self.llvm_target.split('-').collect::<Vec<_>>();
+        if let [_, vendor, _, env] = components[..] {
+            if env.starts_with("musl") && self.target_env != "musl" {
+                return Err(format!(
+                    "target `{}` uses a musl LLVM target but has `target-env` set to `{}`",
+                    self.llvm_target, self.target_env
+                ));
+            }
+            // This usually means a vendor target was derived from an upstream
+            // one without updating `llvm_target`. Other vendors in the LLVM
+            // target are fine, LLVM doesn't care about them.
+            if self.target_env == "musl" && self.target_vendor != "unknown" && vendor == "unknown" {
+                return Err(format!(
+                    "target `{}` has `target-vendor` set to `{}`, but its LLVM target \
+                     still has the `unknown` vendor",
+                    self.llvm_target, self.target_vendor
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Loads a target descriptor from a JSON object.
     pub fn from_json(obj: Json) -> TargetResult {
         // While ugly, this code must remain this way to retain
@@ -1256,7 +1295,9 @@ impl Target {
         fn load_file(path: &Path) -> Result<Target, String> {
             let contents = fs::read(path).map_err(|e| e.to_string())?;
             let obj = json::from_reader(&mut &contents[..]).map_err(|e| e.to_string())?;
-            Target::from_json(obj)
+            let target = Target::from_json(obj)?;
+            target.validate()?;
+            Ok(target)
         }
 
         match *target_triple {
@@ -0,0 +1,18 @@
+use crate::spec::{LinkerFlavor, TargetResult};
+
+pub fn target() -> TargetResult {
+    let mut base = super::powerpc64le_unknown_linux_musl::target()?;
+    base.llvm_target = "powerpc64le-foxkit-linux-musl".to_string();
+    base.target_vendor = "foxkit".to_string();
+    base.options.crt_static_default = false;
+
+    // musl's libc doesn't provide `__stack_chk_fail_local`, which C code
+    // built with the system toolchain's default stack protector expects, so
+    // link in the support library that does. `--as-needed` is re-asserted
+    // first since arguments passed earlier may have turned it off.
+    let post_link_args = base.options.post_link_args.entry(LinkerFlavor::Gcc).or_default();
+    post_link_args.push("-Wl,--as-needed".to_string());
+    post_link_args.push("-lssp_nonshared".to_string());
+
+    Ok(base)
+}
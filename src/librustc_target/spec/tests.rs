@@ -0,0 +1,104 @@
+use super::*;
+
+#[test]
+fn dynamic_musl() {
+    let musl = load_specific("x86_64-unknown-linux-musl").unwrap();
+    assert!(!musl.supports_dynamic_musl());
+
+    let mut dynamic = musl.clone();
+    dynamic.options.crt_static_default = false;
+    assert!(dynamic.supports_dynamic_musl());
+
+    let gnu = load_specific("x86_64-unknown-linux-gnu").unwrap();
+    assert!(!gnu.supports_dynamic_musl());
+
+    for &(vendor, upstream) in &[
+        ("i586-gentoo-linux-musl", "i586-unknown-linux-musl"),
+        ("powerpc64le-foxkit-linux-musl", "powerpc64le-unknown-linux-musl"),
+    ] {
+        assert!(load_specific(vendor).unwrap().supports_dynamic_musl(), "{}", vendor);
+        assert!(!load_specific(upstream).unwrap().supports_dynamic_musl(), "{}", upstream);
+    }
+}
+
+#[test]
+fn i586_gentoo_linux_musl() {
+    let base = load_specific("i586-unknown-linux-musl").unwrap();
+    let gentoo = load_specific("i586-gentoo-linux-musl").unwrap();
+    assert_eq!(gentoo.llvm_target, "i586-gentoo-linux-musl");
+    assert_eq!(gentoo.target_vendor, "gentoo");
+    assert!(gentoo.supports_dynamic_musl());
+
+    // i586 differs from i686 in not assuming SSE2, so the CPU must carry over.
+    assert_eq!(gentoo.options.cpu, "pentium");
+    assert_eq!(gentoo.options.cpu, base.options.cpu);
+    assert_eq!(gentoo.options.features, base.options.features);
+    assert_eq!(gentoo.data_layout, base.data_layout);
+}
+
+#[test]
+fn powerpc64le_foxkit_linux_musl() {
+    let base = load_specific("powerpc64le-unknown-linux-musl").unwrap();
+    let foxkit = load_specific("powerpc64le-foxkit-linux-musl").unwrap();
+    assert_eq!(foxkit.llvm_target, "powerpc64le-foxkit-linux-musl");
+    assert_eq!(foxkit.target_vendor, "foxkit");
+    assert!(foxkit.supports_dynamic_musl());
+    assert_eq!(
+        foxkit.options.post_link_args[&LinkerFlavor::Gcc],
+        ["-Wl,--as-needed", "-lssp_nonshared"]
+    );
+
+    // The CPU (and with it VSX/AltiVec) and little-endian layout come from
+    // the base target.
+    assert_eq!(foxkit.options.cpu, base.options.cpu);
+    assert_eq!(foxkit.options.features, base.options.features);
+    assert_eq!(foxkit.target_endian, "little");
+    assert_eq!(foxkit.data_layout, base.data_layout);
+}
+
+#[test]
+fn vendor_targets_full_relro() {
+    // `RelroLevel::Full` makes the linker pass `-z relro -z now`, which distros
+    // expect from hardened builds. It comes from `linux_base`.
+    for triple in &["i586-gentoo-linux-musl", "powerpc64le-foxkit-linux-musl"] {
+        let target = load_specific(triple).unwrap();
+        assert_eq!(target.options.relro_level, RelroLevel::Full, "{}", triple);
+    }
+}
+
+#[test]
+fn vendor_target_spec_printed_json() {
+    // `test_json_encode_decode` covers the `Json` round trip. On top of that
+    // `x.py dist target-specs` writes out what `--print target-spec-json`
+    // prints, which has to parse back from text as a custom target.
+    let target = load_specific("i586-gentoo-linux-musl").unwrap();
+    let printed = format!("{}\n", target.to_json().pretty());
+    let parsed = Target::from_json(printed.parse().unwrap()).unwrap();
+    assert_eq!(target, parsed);
+}
+
+#[test]
+fn validate_builtin_targets() {
+    for triple in TARGETS {
+        if let Ok(target) = load_specific(triple) {
+            assert_eq!(target.validate(), Ok(()), "{}", triple);
+        }
+    }
+}
+
+#[test]
+fn validate_musl_vendor_mismatch() {
+    let gentoo = load_specific("i586-gentoo-linux-musl").unwrap();
+
+    let mut upstream_triple = gentoo.clone();
+    upstream_triple.llvm_target = "i586-unknown-linux-musl".to_string();
+    assert!(upstream_triple.validate().is_err());
+
+    let mut other_vendor = gentoo.clone();
+    other_vendor.llvm_target = "i586-pc-linux-musl".to_string();
+    assert_eq!(other_vendor.validate(), Ok(()));
+
+    let mut wrong_env = gentoo.clone();
+    wrong_env.target_env = "gnu".to_string();
+    assert!(wrong_env.validate().is_err());
+}
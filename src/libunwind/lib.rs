@@ -19,8 +19,12 @@ cfg_if::cfg_if! {
 }
 
 #[cfg(target_env = "musl")]
-#[link(name = "unwind", kind = "static", cfg(target_feature = "crt-static"))]
-#[link(name = "gcc_s", cfg(not(target_feature = "crt-static")))]
+#[link(
+    name = "unwind",
+    kind = "static",
+    cfg(any(target_feature = "crt-static", feature = "llvm-libunwind"))
+)]
+#[link(name = "gcc_s", cfg(not(any(target_feature = "crt-static", feature = "llvm-libunwind"))))]
 extern "C" {}
 
 #[cfg(target_os = "redox")]
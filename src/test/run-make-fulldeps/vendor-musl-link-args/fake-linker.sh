@@ -0,0 +1,4 @@
+#!/bin/sh
+
+# Records the arguments it is called with instead of linking anything.
+printf '%s\n' "$@" > "$TMPDIR/linker-args"